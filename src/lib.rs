@@ -11,13 +11,21 @@
 //! ## Key Features
 //! - **The Entry Point**: `#[webio_main]` transforms async entry points into high-efficiency 
 //!   execution units managed by the WebIO engine.
-//! - **Template Engine**: `replace!` and `html!` provide zero-dependency string substitution 
+//! - **Template Engine**: `replace!` and `html!` provide zero-dependency string substitution
 //!   at the compilation phase, optimized for raw string literals and web content.
+//! - **Buffered Rendering**: `replace_to!` and `html_to!` write straight into a caller-owned
+//!   buffer, avoiding intermediate `String` allocations in latency-sensitive render loops.
+//! - **JSX-style Markup**: `html!` also accepts an XML-like element tree (e.g.
+//!   `html! { <div class={cls}>{text}</div> }`) and lowers it to string-building code.
+//! - **File Templates**: `html_file!` loads a `.html` file relative to the crate root
+//!   at compile time and runs it through the same substitution engine as `html!`.
+//! - **Console Logging**: `console!`/`debug!`/`info!`/`error!` expand `format!`-style
+//!   arguments straight into a call into the WebIO runtime's logging bridge.
 
 #![doc = include_str!("../README.md")]
 
 extern crate proc_macro;
-use proc_macro::TokenStream;
+use proc_macro::{Delimiter, TokenStream, TokenTree};
 
 /// # WebIO Main Entry Point Macro
 /// 
@@ -78,13 +86,61 @@ pub fn webio_main(_attr: TokenStream, item: TokenStream) -> TokenStream {
 /// 
 /// **WebIO Zero-Dependency Template Engine**
 ///
-/// The `replace` macro takes a template string (content) along with key-value pairs 
+/// The `replace` macro takes a template string (content) along with key-value pairs
 /// and substitutes placeholders (formatted as `{{key}}`) with their corresponding values.
-/// 
-/// Built with a **zero-dependency philosophy**, it performs efficient string 
-/// transformations during the compilation phase, making it ideal for high-performance 
+///
+/// Built with a **zero-dependency philosophy**, it performs efficient string
+/// transformations during the compilation phase, making it ideal for high-performance
 /// WebIO applications where latency is critical.
 ///
+/// ## Compile-Time Placeholder Checking
+/// When `$content` is a string literal (not a variable), the macro scans it for
+/// `{{key}}` placeholders at expansion time and cross-checks them against the
+/// provided keys: a placeholder with no matching key, or a key with no matching
+/// placeholder, is a `compile_error!` instead of a silently wrong render. This
+/// check is skipped when `$content` is a runtime expression, since its text isn't
+/// known at compile time.
+/// ```rust,compile_fail
+/// use webio_macros::replace;
+///
+/// // Typo: the placeholder is `{{nmae}}`, the key is `name` — this fails to compile.
+/// let result = replace!("Hello {{nmae}}!", name = "Developer");
+/// ```
+///
+/// ## Conditional and Loop Sections
+/// Literal templates may also use Handlebars-style sections: `{{#if cond}}...{{/if}}`,
+/// `{{#if cond}}...{{else}}...{{/if}}`, and `{{#each items}}...{{/each}}`. The
+/// `cond` and `items` identifiers bind to `key = expr` arguments exactly like a
+/// plain placeholder does — `cond` to a `bool` expression, `items` to something
+/// `for`-loopable. Inside an `{{#each}}` block, `{{this}}` (or `{{.}}`) refers to
+/// the current element, including as an `{{#if}}`'s `cond` or a nested
+/// `{{#each}}`'s `items`.
+/// ```rust
+/// use webio_macros::replace;
+///
+/// let names = vec!["a", "b"];
+/// let out = replace!(
+///     "{{#if show}}Hi {{name}}{{else}}Bye{{/if}}! {{#each items}}[{{this}}]{{/each}}",
+///     show = true, name = "Ada", items = names
+/// );
+///
+/// assert_eq!(out, "Hi Ada! [a][b]");
+/// ```
+///
+/// `{{this}}` as a section's own condition or collection, naming the current
+/// `{{#each}}` element rather than a macro argument:
+/// ```rust
+/// use webio_macros::replace;
+///
+/// let flags = vec![true, false];
+/// let out = replace!(
+///     "{{#each items}}{{#if this}}yes{{else}}no{{/if}} {{/each}}",
+///     items = flags
+/// );
+///
+/// assert_eq!(out, "yes no ");
+/// ```
+///
 /// ## Parameters
 /// - `$content`: The template string or variable containing placeholders (e.g., `"Hello {{name}}"`).
 /// - `$key`: The identifier matching the placeholder name inside the braces (e.g., `name`).
@@ -96,10 +152,21 @@ pub fn webio_main(_attr: TokenStream, item: TokenStream) -> TokenStream {
 ///
 /// let template = "<p>Hello, {{name}}! Welcome to {{platform}}.</p>";
 /// let result = replace!(template, name = "Developer", platform = "WebIO");
-/// 
+///
 /// assert_eq!(result, "<p>Hello, Developer! Welcome to WebIO.</p>");
 /// ```
 ///
+/// A template literal written directly in the macro call is free to contain commas
+/// of its own — argument splitting only looks at top-level commas between `$content`
+/// and the `$key = $val` pairs, not at the characters inside a string literal.
+/// ```rust
+/// use webio_macros::replace;
+///
+/// let result = replace!("Hello, {{name}}!", name = "Ada");
+///
+/// assert_eq!(result, "Hello, Ada!");
+/// ```
+///
 /// ## Handling Raw Strings
 /// The macro works seamlessly with Rust's raw strings, which is perfect for 
 /// embedding code or HTML without escaping quotes:
@@ -138,35 +205,35 @@ pub fn webio_main(_attr: TokenStream, item: TokenStream) -> TokenStream {
 /// ```
 #[proc_macro]
 pub fn replace(input: TokenStream) -> TokenStream {
-    let input_str = input.to_string();
-    
-    let mut parts = input_str.splitn(2, ',');
-    let template = parts.next().unwrap_or("\"\"").trim();
-    let remaining = parts.next().unwrap_or("");
+    let segments = split_top_level_commas(input);
+    let template = segments
+        .first()
+        .map(|ts| ts.to_string())
+        .unwrap_or_else(|| "\"\"".to_string());
+    let template = template.trim();
+    let pairs = pairs_from_segments(&segments[1..]);
 
-    let mut output_code = format!("{{ let mut content_string = {}.to_string();", template);
+    if let Some(literal) = parse_str_literal(template) {
+        return render_template_literal(&literal, &pairs, false, "replace");
+    }
 
-    for pair in remaining.split(',') {
-        let pair = pair.trim();
-        if pair.is_empty() { continue; }
+    // Non-literal template: its text isn't known at compile time, so fall back to
+    // runtime substitution (no section support, since sections need the literal text).
+    let mut output_code = format!("{{ let mut content_string = {}.to_string();", template);
 
-        if let Some((key, val)) = pair.split_once('=') {
-            let key = key.trim();
-            let val = val.trim();
+    for (key, val) in &pairs {
+        // 1. Build the search pattern "{{key}}" safely
+        // We use string interpolation to create "{{name}}"
+        let pattern = "{{".to_string() + key + "}}";
 
-            // 1. Build the search pattern "{{key}}" safely
-            // We use string interpolation to create "{{name}}"
-            let pattern = format!("{{{{") + key + "}}";
+        // 2. Build the replacement line
+        // We use r#""# (raw strings) to make the code generated easy to read
+        let line = format!(
+            r#"content_string = content_string.replace("{}", &format!("{{}}", {}));"#,
+            pattern, val
+        );
 
-            // 2. Build the replacement line
-            // We use r#""# (raw strings) to make the code generated easy to read
-            let line = format!(
-                r#"content_string = content_string.replace("{}", &format!("{{}}", {}));"#,
-                pattern, val
-            );
-            
-            output_code.push_str(&line);
-        }
+        output_code.push_str(&line);
     }
 
     output_code.push_str(" content_string }");
@@ -174,19 +241,22 @@ pub fn replace(input: TokenStream) -> TokenStream {
 }
 
 /// ### html!($content, $key = $val, ...)
-/// 
+///
 /// **WebIO Semantic HTML Template Macro**
 ///
-/// The `html` macro is a specialized alias for `replace!`. It is designed to improve 
-/// code readability when generating HTML structures within the WebIO framework.
-/// 
-/// It substitutes `{{key}}` placeholders with dynamic values, allowing for 
-/// clean, logic-less HTML templates that are processed at high speed.
+/// The `html` macro substitutes `{{key}}` placeholders with dynamic values, allowing
+/// for clean, logic-less HTML templates that are processed at high speed.
+///
+/// Unlike `replace!`, every substituted value is **HTML-escaped by default**: `&`, `<`,
+/// `>`, `"`, and `'` are turned into their entity equivalents before being spliced into
+/// the template. This makes it safe to interpolate user-supplied strings directly
+/// without opening up an XSS hole.
 ///
 /// ### Parameters
 /// - `$content`: The HTML template string (often used with raw strings `r#""#`).
 /// - `$key`: The identifier for the HTML placeholder (e.g., `title`, `body`).
 /// - `$val`: The content to inject into the HTML (e.g., `user_input` or a static string).
+///   Wrap a value in `raw(...)` to splice it in unescaped when it is already trusted HTML.
 ///
 /// ### Examples
 /// ```rust
@@ -194,15 +264,1122 @@ pub fn replace(input: TokenStream) -> TokenStream {
 ///
 /// let user = "Ahmed";
 /// let card = html!(r#"<div class="user">{{name}}</div>"#, name = user);
-/// 
+///
 /// assert_eq!(card, r#"<div class="user">Ahmed</div>"#);
 /// ```
-/// 
-/// Using `html!` alongside `webio_main` allows for rapid UI generation 
+///
+/// ### Escaping untrusted input
+/// ```rust
+/// use webio_macros::html;
+///
+/// let comment = "<script>alert(1)</script>";
+/// let out = html!("<p>{{comment}}</p>", comment = comment);
+///
+/// assert_eq!(out, "<p>&lt;script&gt;alert(1)&lt;/script&gt;</p>");
+/// ```
+///
+/// ### Opting out with `raw(...)`
+/// ```rust
+/// use webio_macros::html;
+///
+/// let fragment = "<b>trusted</b>";
+/// let out = html!("<div>{{body}}</div>", body = raw(fragment));
+///
+/// assert_eq!(out, "<div><b>trusted</b></div>");
+/// ```
+///
+/// Using `html!` alongside `webio_main` allows for rapid UI generation
 /// without the overhead of heavy template engines.
+///
+/// ## JSX-style Element Trees
+/// When the input starts with `<`, `html!` switches to parsing an XML-like element
+/// tree instead of a flat template: tag-open (`<ident attr="...">`), tag-close
+/// (`</ident>`), self-closing (`<ident .../>`), `{expr}` children, and attribute
+/// values of either a string literal or a braced expression. Every `{expr}`
+/// interpolation &mdash; attribute or child &mdash; is HTML-escaped by default, with the
+/// same `raw(...)` opt-out as the flat form. A mismatched closing tag is a
+/// `compile_error!` naming the offending tag.
+/// ```rust
+/// use webio_macros::html;
+///
+/// let cls = "card";
+/// let text = "Hi";
+/// let out = html! { <div class={cls}><span>{text}</span></div> };
+///
+/// assert_eq!(out, r#"<div class="card"><span>Hi</span></div>"#);
+/// ```
+///
+/// Literal text children keep the spacing and punctuation of the source as written.
+/// ```rust
+/// use webio_macros::html;
+///
+/// let out = html! { <p>Hello, world! Welcome to our site.</p> };
+///
+/// assert_eq!(out, "<p>Hello, world! Welcome to our site.</p>");
+/// ```
+///
+/// Hyphens, percents, ampersands, and slashes are common enough in real markup
+/// (prices, word pairs, "Terms & Conditions") to also glue to their neighbors
+/// without an inserted space.
+/// ```rust
+/// use webio_macros::html;
+///
+/// let out = html! { <p>high-performance 50% off Q&A A/B</p> };
+///
+/// assert_eq!(out, "<p>high-performance 50% off Q&A A/B</p>");
+/// ```
+///
+/// ## Conditional and Loop Sections
+/// The flat form supports the same `{{#if}}`/`{{#each}}` sections as [`replace!`],
+/// with escaping applied to every interpolated value as usual.
+/// ```rust
+/// use webio_macros::html;
+///
+/// let items = vec!["<b>x</b>", "y"];
+/// let out = html!("{{#each items}}<li>{{this}}</li>{{/each}}", items = items);
+///
+/// assert_eq!(out, "<li>&lt;b&gt;x&lt;/b&gt;</li><li>y</li>");
+/// ```
 #[proc_macro]
 pub fn html(input: TokenStream) -> TokenStream {
-    // Leverages the core replacement engine to provide a domain-specific HTML macro.
-    // Acts as a semantic alias by proxying input to the core replacement engine.
-    replace(input)
+    let tokens: Vec<TokenTree> = input.clone().into_iter().collect();
+    if matches!(tokens.first(), Some(TokenTree::Punct(p)) if p.as_char() == '<') {
+        return html_jsx(&tokens);
+    }
+
+    let segments = split_top_level_commas(input);
+    let template = segments
+        .first()
+        .map(|ts| ts.to_string())
+        .unwrap_or_else(|| "\"\"".to_string());
+    let template = template.trim();
+    let pairs = pairs_from_segments(&segments[1..]);
+
+    if let Some(literal) = parse_str_literal(template) {
+        return render_template_literal(&literal, &pairs, true, "html");
+    }
+
+    // Non-literal template: its text isn't known at compile time, so fall back to
+    // runtime substitution (no section support, since sections need the literal text).
+    let mut output_code = format!(
+        "{{ {} let mut content_string = {}.to_string();",
+        HTML_ESCAPE_FN, template
+    );
+
+    for (key, val) in &pairs {
+        // Build the search pattern "{{key}}" safely
+        let pattern = "{{".to_string() + key + "}}";
+
+        // `raw(expr)` opts a value out of escaping for already-trusted HTML fragments.
+        let line = if let Some(inner) = val.strip_prefix("raw(").and_then(|v| v.strip_suffix(')')) {
+            format!(
+                r#"content_string = content_string.replace("{}", &format!("{{}}", {}));"#,
+                pattern, inner
+            )
+        } else {
+            format!(
+                r#"content_string = content_string.replace("{}", &__webio_html_escape(&format!("{{}}", {})));"#,
+                pattern, val
+            )
+        };
+
+        output_code.push_str(&line);
+    }
+
+    output_code.push_str(" content_string }");
+    output_code.parse().expect("Failed to parse html macro")
+}
+
+/// Lowers an `html! { <tag>...</tag> }` element tree into string-building code.
+///
+/// Parses `tokens` as a sequence of sibling nodes (elements, `{expr}` children,
+/// and literal text), then emits code that builds the result into a local
+/// `content_string` buffer, mirroring the push/write style of [`generate_buffered`].
+fn html_jsx(tokens: &[TokenTree]) -> TokenStream {
+    let mut pos = 0;
+    let body = match jsx_parse_nodes(tokens, &mut pos, None) {
+        Ok(code) => code,
+        Err(err) => return compile_error(&err),
+    };
+
+    let output = format!(
+        "{{ {} use ::std::fmt::Write as _; let mut content_string = String::new(); {} content_string }}",
+        HTML_ESCAPE_FN, body
+    );
+    output.parse().expect("Failed to parse html! JSX macro")
+}
+
+/// Parses sibling nodes starting at `*pos`, appending generated code to build each
+/// into `content_string`, until either a matching `</closing>` tag is consumed (when
+/// `closing` is `Some`) or the token stream runs out (when `closing` is `None`, i.e.
+/// at the root). Returns an error message on unclosed, mismatched, or otherwise
+/// malformed markup.
+fn jsx_parse_nodes(
+    tokens: &[TokenTree],
+    pos: &mut usize,
+    closing: Option<&str>,
+) -> Result<String, String> {
+    let mut code = String::new();
+
+    loop {
+        match tokens.get(*pos) {
+            None => {
+                return match closing {
+                    Some(tag) => Err(format!("unclosed <{}>: expected a matching </{}>", tag, tag)),
+                    None => Ok(code),
+                };
+            }
+            Some(TokenTree::Punct(p)) if p.as_char() == '<' => {
+                let is_closing_tag =
+                    matches!(tokens.get(*pos + 1), Some(TokenTree::Punct(p2)) if p2.as_char() == '/');
+                if is_closing_tag {
+                    let (close_tag, new_pos) = jsx_parse_closing_tag(tokens, *pos)?;
+                    return match closing {
+                        Some(tag) if tag == close_tag => {
+                            *pos = new_pos;
+                            Ok(code)
+                        }
+                        Some(tag) => Err(format!(
+                            "mismatched closing tag: expected </{}>, found </{}>",
+                            tag, close_tag
+                        )),
+                        None => Err(format!(
+                            "closing tag </{}> has no matching open tag",
+                            close_tag
+                        )),
+                    };
+                }
+                code.push_str(&jsx_parse_element(tokens, pos)?);
+            }
+            Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => {
+                code.push_str(&jsx_interpolate("content_string", "{}", g.stream()));
+                *pos += 1;
+            }
+            Some(_) => {
+                let mut text = String::new();
+                let mut prev_glues_next = true;
+                while let Some(tt) = tokens.get(*pos) {
+                    let is_boundary = matches!(tt, TokenTree::Punct(p) if p.as_char() == '<')
+                        || matches!(tt, TokenTree::Group(g) if g.delimiter() == Delimiter::Brace);
+                    if is_boundary {
+                        break;
+                    }
+                    if !text.is_empty() && !prev_glues_next && !glues_to_prev(tt) {
+                        text.push(' ');
+                    }
+                    text.push_str(&tt.to_string());
+                    prev_glues_next = glues_to_next(tt);
+                    *pos += 1;
+                }
+                code.push_str(&format!(
+                    "content_string.push_str(\"{}\");",
+                    escape_rust_string(&text)
+                ));
+            }
+        }
+    }
+}
+
+/// Whether `tt` is punctuation that should hug the token *before* it, with no
+/// inserted space (e.g. the `,` in `Hello, world`, or the `%` in `50%`).
+/// Brackets/braces/parens never show up here as bare `Punct`s (they're `Group`
+/// delimiters), so only punctuation that can actually occur loose in JSX text
+/// needs listing. This is a fixed allow-list, not real source-span whitespace
+/// detection (stable `proc_macro::Span` exposes neither `byte_range()` nor a
+/// way to join two spans), so punctuation outside this list — and outside
+/// [`glues_to_next`] — still gets an inserted space on both sides. It's also
+/// necessarily a guess in the other direction: `-`/`+`/`&`/`/` glue tight on
+/// *both* sides (see [`glues_to_next`]) because that's how they show up in
+/// ordinary markup text (`high-performance`, `Q&A`, `A/B`), but the same
+/// characters used as spaced-out operators (`1 + 1`, `Score: -5`) will lose
+/// their surrounding spaces too — there's no way to tell the two apart
+/// without the source spans this heuristic doesn't have access to.
+fn glues_to_prev(tt: &TokenTree) -> bool {
+    matches!(
+        tt,
+        TokenTree::Punct(p) if matches!(
+            p.as_char(),
+            ',' | '.' | '!' | '?' | ';' | ':' | '%' | '-' | '&' | '/' | '+' | '@'
+        )
+    )
+}
+
+/// Whether `tt` is punctuation that the *next* token should hug, with no space
+/// inserted after it (e.g. an apostrophe in `it's`, the `#` in `#hashtag`, or
+/// the `-`/`&`/`/`/`+`/`@` pairs [`glues_to_prev`] also hugs from the other
+/// side, so those glue tight on both sides).
+fn glues_to_next(tt: &TokenTree) -> bool {
+    matches!(tt, TokenTree::Punct(p) if matches!(p.as_char(), '\'' | '#' | '-' | '&' | '/' | '+' | '@'))
+}
+
+/// Parses one element starting at a `<` token, including its attributes and
+/// (unless self-closing) its children and matching closing tag.
+fn jsx_parse_element(tokens: &[TokenTree], pos: &mut usize) -> Result<String, String> {
+    *pos += 1; // consume '<'
+    let tag = match tokens.get(*pos) {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return Err("expected a tag name after `<`".to_string()),
+    };
+    *pos += 1;
+
+    let mut code = format!("content_string.push_str(\"<{}\");", tag);
+    let mut self_closing = false;
+
+    loop {
+        match tokens.get(*pos) {
+            Some(TokenTree::Punct(p)) if p.as_char() == '>' => {
+                *pos += 1;
+                break;
+            }
+            Some(TokenTree::Punct(p)) if p.as_char() == '/' => {
+                match tokens.get(*pos + 1) {
+                    Some(TokenTree::Punct(p2)) if p2.as_char() == '>' => {
+                        *pos += 2;
+                        self_closing = true;
+                        break;
+                    }
+                    _ => return Err(format!("expected `/>` to self-close <{}>", tag)),
+                }
+            }
+            Some(TokenTree::Ident(ident)) => {
+                let attr_name = ident.to_string();
+                *pos += 1;
+                match tokens.get(*pos) {
+                    Some(TokenTree::Punct(p)) if p.as_char() == '=' => *pos += 1,
+                    _ => {
+                        return Err(format!(
+                            "expected `=` after attribute `{}` on <{}>",
+                            attr_name, tag
+                        ))
+                    }
+                }
+                match tokens.get(*pos) {
+                    Some(TokenTree::Literal(lit)) => {
+                        let value = parse_str_literal(&lit.to_string()).ok_or_else(|| {
+                            format!(
+                                "attribute `{}` on <{}> must be a string literal or a {{expr}}",
+                                attr_name, tag
+                            )
+                        })?;
+                        let literal_text = format!(" {}=\"{}\"", attr_name, value);
+                        code.push_str(&format!(
+                            "content_string.push_str(\"{}\");",
+                            escape_rust_string(&literal_text)
+                        ));
+                        *pos += 1;
+                    }
+                    Some(TokenTree::Group(g)) if g.delimiter() == Delimiter::Brace => {
+                        // `prefix` holds `\"` (escaped quote) rather than a bare `"`,
+                        // since it is spliced directly into the generated write! format string.
+                        let prefix = format!(" {}=\\\"", attr_name);
+                        code.push_str(&jsx_interpolate(
+                            "content_string",
+                            &format!("{}{{}}\\\"", prefix),
+                            g.stream(),
+                        ));
+                        *pos += 1;
+                    }
+                    _ => {
+                        return Err(format!(
+                            "expected a value for attribute `{}` on <{}>",
+                            attr_name, tag
+                        ))
+                    }
+                }
+            }
+            None => return Err(format!("unterminated tag <{}>: expected `>` or `/>`", tag)),
+            _ => return Err(format!("unexpected token while parsing attributes of <{}>", tag)),
+        }
+    }
+
+    if self_closing {
+        code.push_str("content_string.push_str(\"/>\");");
+        return Ok(code);
+    }
+
+    code.push_str("content_string.push_str(\">\");");
+    code.push_str(&jsx_parse_nodes(tokens, pos, Some(&tag))?);
+    code.push_str(&format!("content_string.push_str(\"</{}>\");", tag));
+    Ok(code)
+}
+
+/// Parses a `</ident>` closing tag starting at `tokens[pos]` (the opening `<`),
+/// returning the tag name and the position just past the final `>`.
+fn jsx_parse_closing_tag(tokens: &[TokenTree], pos: usize) -> Result<(String, usize), String> {
+    let tag = match tokens.get(pos + 2) {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return Err("expected a tag name after `</`".to_string()),
+    };
+    match tokens.get(pos + 3) {
+        Some(TokenTree::Punct(p)) if p.as_char() == '>' => Ok((tag, pos + 4)),
+        _ => Err(format!("expected `>` to close </{}", tag)),
+    }
+}
+
+/// Emits a `write!` statement that interpolates a `{expr}` node into `buf`, using
+/// `format_str` as the `write!` format string (e.g. `"{}"` for a plain child, or
+/// `" class=\"{}\""` for an attribute). Honors a `raw(...)` wrapper around `expr`
+/// as an escaping opt-out, the same as the flat `html!` form.
+fn jsx_interpolate(buf: &str, format_str: &str, expr: TokenStream) -> String {
+    let (inner, is_raw) = strip_raw_wrapper(expr);
+    let expr = inner.to_string();
+
+    if is_raw {
+        format!(r#"write!({}, "{}", {}).unwrap();"#, buf, format_str, expr)
+    } else {
+        format!(
+            r#"write!({}, "{}", __webio_html_escape(&format!("{{}}", {}))).unwrap();"#,
+            buf, format_str, expr
+        )
+    }
+}
+
+/// Recognizes a `raw(expr)` wrapper at the token level (an `ident` named `raw`
+/// followed by a single parenthesized group) and, if found, returns the inner
+/// expression's tokens along with `true`. Otherwise returns `stream` unchanged
+/// with `false`.
+fn strip_raw_wrapper(stream: TokenStream) -> (TokenStream, bool) {
+    let tokens: Vec<TokenTree> = stream.clone().into_iter().collect();
+    if let [TokenTree::Ident(ident), TokenTree::Group(group)] = tokens.as_slice() {
+        if ident.to_string() == "raw" && group.delimiter() == Delimiter::Parenthesis {
+            return (group.stream(), true);
+        }
+    }
+    (stream, false)
+}
+
+/// Inline HTML entity escaper spliced into the code generated by [`html!`].
+///
+/// Escapes `&`, `<`, `>`, `"`, and `'` (ampersand first, so existing entities
+/// aren't double-escaped) before a value is substituted into a template.
+const HTML_ESCAPE_FN: &str = r#"fn __webio_html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#x27;")
+}"#;
+
+/// ### replace_to!($buf, $content, $key = $val, ...)
+///
+/// **Buffered WebIO Template Engine**
+///
+/// The buffered sibling of [`replace!`]. Instead of returning a freshly allocated
+/// `String`, it writes the substituted output directly into `$buf` (any
+/// `&mut String` or other `std::fmt::Write` target).
+///
+/// When `$content` is a string literal, the template is scanned for `{{key}}`
+/// delimiters once, at compile time: literal segments are emitted as
+/// `buf.push_str(...)` and placeholders as `write!(buf, "{}", val)`, so expansion
+/// is a single pass with no intermediate `String` allocations. When `$content` is
+/// a non-literal expression, it falls back to building the result once via
+/// `replace!`-style substitution and appending it to `$buf`.
+///
+/// ## Examples
+/// ```rust
+/// use webio_macros::replace_to;
+///
+/// let mut buf = String::new();
+/// replace_to!(&mut buf, "Hello, {{name}}!", name = "Developer");
+///
+/// assert_eq!(buf, "Hello, Developer!");
+/// ```
+///
+/// Reusing `buf` across calls avoids repeated allocation in hot render loops:
+/// ```rust
+/// use webio_macros::replace_to;
+///
+/// let mut buf = String::new();
+/// for name in ["Alice", "Bob"] {
+///     buf.clear();
+///     replace_to!(&mut buf, "Hi {{name}}", name = name);
+///     println!("{buf}");
+/// }
+/// ```
+#[proc_macro]
+pub fn replace_to(input: TokenStream) -> TokenStream {
+    let segments = split_top_level_commas(input);
+    let buf = segments.first().map(|ts| ts.to_string()).unwrap_or_default();
+    let template = segments
+        .get(1)
+        .map(|ts| ts.to_string())
+        .unwrap_or_else(|| "\"\"".to_string());
+    let pairs = pairs_from_segments(segments.get(2..).unwrap_or_default());
+
+    generate_buffered(buf.trim(), template.trim(), &pairs, false)
+}
+
+/// ### html_to!($buf, $content, $key = $val, ...)
+///
+/// **Buffered WebIO HTML Template Macro**
+///
+/// The buffered sibling of [`html!`]: it writes HTML-escaped output directly into
+/// `$buf` instead of allocating a new `String`, using the same single-pass,
+/// compile-time delimiter scan as [`replace_to!`]. Values are escaped by default;
+/// wrap one in `raw(...)` to splice already-trusted HTML in unescaped.
+///
+/// ## Examples
+/// ```rust
+/// use webio_macros::html_to;
+///
+/// let mut buf = String::new();
+/// html_to!(&mut buf, "<p>{{comment}}</p>", comment = "<script>alert(1)</script>");
+///
+/// assert_eq!(buf, "<p>&lt;script&gt;alert(1)&lt;/script&gt;</p>");
+/// ```
+#[proc_macro]
+pub fn html_to(input: TokenStream) -> TokenStream {
+    let segments = split_top_level_commas(input);
+    let buf = segments.first().map(|ts| ts.to_string()).unwrap_or_default();
+    let template = segments
+        .get(1)
+        .map(|ts| ts.to_string())
+        .unwrap_or_else(|| "\"\"".to_string());
+    let pairs = pairs_from_segments(segments.get(2..).unwrap_or_default());
+
+    generate_buffered(buf.trim(), template.trim(), &pairs, true)
+}
+
+/// ### html_file!($path, $key = $val, ...)
+///
+/// **Compile-Time HTML File Template Macro**
+///
+/// Loads an external `.html` file, relative to the crate root (`CARGO_MANIFEST_DIR`),
+/// at compile time and runs its contents through the same substitution engine as
+/// [`html!`]: `{{key}}` placeholders, `{{#if}}`/`{{#each}}` sections, and HTML
+/// escaping (with a `raw(...)` opt-out) all work exactly as they do for an inline
+/// template literal. This lets designers keep markup in a real `.html` file instead
+/// of a Rust string literal, with no runtime file I/O: every substitution/validation
+/// error — a missing file, an unbalanced section, an unknown placeholder — surfaces
+/// as a compile error.
+///
+/// `$path` must be a string literal, since the file has to be read during macro
+/// expansion. Note that Cargo only knows to re-run this macro when one of the
+/// crate's `.rs` files changes; editing the `.html` file alone won't trigger a
+/// rebuild (touch a source file, or `cargo build` with `--force`/a clean build, to
+/// pick up template-only changes).
+///
+/// ## Examples
+/// ```rust,ignore
+/// use webio_macros::html_file;
+///
+/// // Resolved relative to this crate's `CARGO_MANIFEST_DIR`; ignored here since
+/// // doctests don't ship a `templates/card.html` alongside them.
+/// let card = html_file!("templates/card.html", name = "Ada", title = "Profile");
+/// ```
+#[proc_macro]
+pub fn html_file(input: TokenStream) -> TokenStream {
+    let segments = split_top_level_commas(input);
+    let path_token = segments
+        .first()
+        .map(|ts| ts.to_string())
+        .unwrap_or_else(|| "\"\"".to_string());
+    let pairs = pairs_from_segments(&segments[1..]);
+
+    let relative_path = match parse_str_literal(path_token.trim()) {
+        Some(path) => path,
+        None => return compile_error("html_file! requires a string literal path"),
+    };
+
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let full_path = std::path::Path::new(&manifest_dir).join(&relative_path);
+
+    let contents = match std::fs::read_to_string(&full_path) {
+        Ok(contents) => contents,
+        Err(err) => return compile_error(&format!("failed to read `{}`: {}", relative_path, err)),
+    };
+
+    render_template_literal(&contents, &pairs, true, "html_file")
+}
+
+/// # console!($level, $fmt, $arg, ...)
+///
+/// **WebIO Console Logging Macro**
+///
+/// Expands `format!`-style arguments directly into a call into the WebIO runtime's
+/// logging bridge, `::webio::console::log($level, format_args!($fmt, $arg, ...))`,
+/// so callers get `println!`-style ergonomics without manually formatting a string
+/// first. `$level` is any expression the runtime's `log` accepts (e.g.
+/// `::webio::console::Level::Info`).
+///
+/// `$level` is split from the rest of the arguments on the first top-level comma,
+/// same as `$content`/`$key` are in [`replace!`]: commas nested inside a group
+/// (a tuple, a call's parens, a `vec![...]`) don't count, only a bare `,` does.
+///
+/// [`debug!`], [`info!`], and [`error!`] are level-specific shorthands for the
+/// common cases. Since they share their names with the equivalent macros in crates
+/// like `log`/`tracing`, import them explicitly (`use webio_macros::info;`) rather
+/// than with `*`-glob if another logging crate is also in scope.
+///
+/// ### Example:
+/// ```rust
+/// use webio_macros::console;
+///
+/// let user = "Ada";
+/// console!(::webio::console::Level::Info, "user logged in: {}", user);
+/// ```
+#[proc_macro]
+pub fn console(input: TokenStream) -> TokenStream {
+    let segments = split_top_level_commas(input);
+    let level = segments.first().map(|ts| ts.to_string()).unwrap_or_default();
+    let level = level.trim();
+
+    if level.is_empty() {
+        return compile_error("console! requires a level, e.g. console!(Level::Info, \"...\")");
+    }
+
+    let args = segments[1..]
+        .iter()
+        .map(|ts| ts.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    emit_console_call(level, &args)
+}
+
+/// # debug!($fmt, $arg, ...)
+///
+/// Shorthand for [`console!`] at `::webio::console::Level::Debug`.
+///
+/// ### Example:
+/// ```rust
+/// use webio_macros::debug;
+///
+/// let user = "Ada";
+/// debug!("user: {:?}", user);
+/// ```
+#[proc_macro]
+pub fn debug(input: TokenStream) -> TokenStream {
+    emit_console_call("::webio::console::Level::Debug", &input.to_string())
+}
+
+/// # info!($fmt, $arg, ...)
+///
+/// Shorthand for [`console!`] at `::webio::console::Level::Info`.
+///
+/// ### Example:
+/// ```rust
+/// use webio_macros::info;
+///
+/// info!("listening on port {}", 8080);
+/// ```
+#[proc_macro]
+pub fn info(input: TokenStream) -> TokenStream {
+    emit_console_call("::webio::console::Level::Info", &input.to_string())
+}
+
+/// # error!($fmt, $arg, ...)
+///
+/// Shorthand for [`console!`] at `::webio::console::Level::Error`.
+///
+/// ### Example:
+/// ```rust
+/// use webio_macros::error;
+///
+/// error!("request failed: {}", "timeout");
+/// ```
+#[proc_macro]
+pub fn error(input: TokenStream) -> TokenStream {
+    emit_console_call("::webio::console::Level::Error", &input.to_string())
+}
+
+/// Builds the `::webio::console::log($level, format_args!($args))` call shared by
+/// [`console!`] and its [`debug!`]/[`info!`]/[`error!`] shorthands.
+fn emit_console_call(level: &str, args: &str) -> TokenStream {
+    format!("::webio::console::log({}, format_args!({}))", level, args)
+        .parse()
+        .expect("Failed to parse console macro")
+}
+
+/// Shared code generator for the literal-template branch of [`replace!`], [`html!`],
+/// and [`html_file!`]: parses `literal` into a [`TemplateNode`] tree, validates its
+/// placeholders against `pairs`, and lowers it to a `String`-building expression.
+/// `escape` selects HTML-escaped output (as `html!`/`html_file!` want) versus raw
+/// substitution (as `replace!` wants); `macro_name` only appears in the `.expect()`
+/// panic message if the generated code somehow fails to parse.
+fn render_template_literal(
+    literal: &str,
+    pairs: &[(String, String)],
+    escape: bool,
+    macro_name: &str,
+) -> TokenStream {
+    let nodes = match parse_template(literal) {
+        Ok(nodes) => nodes,
+        Err(err) => return compile_error(&err),
+    };
+    if let Some(err) = check_placeholder_coverage(&nodes, pairs) {
+        return compile_error(&err);
+    }
+
+    let mut preamble = String::new();
+    if escape {
+        preamble.push_str(HTML_ESCAPE_FN);
+    }
+    preamble.push_str("use ::std::fmt::Write as _;");
+
+    let body = emit_nodes("content_string", &nodes, pairs, escape);
+    let output = format!(
+        "{{ {} let mut content_string = String::new(); {} content_string }}",
+        preamble, body
+    );
+    output
+        .parse()
+        .unwrap_or_else(|_| panic!("Failed to parse {} macro", macro_name))
+}
+
+/// Shared code generator for [`replace_to!`] and [`html_to!`].
+///
+/// Scans `template` for `{{key}}` delimiters at compile time when it is a string
+/// literal, emitting `push_str`/`write!` calls directly into `buf`. Falls back to
+/// the `replace!`-style runtime substitution, appended to `buf`, when `template`
+/// is a non-literal expression.
+fn generate_buffered(buf: &str, template: &str, pairs: &[(String, String)], escape: bool) -> TokenStream {
+    let mut preamble = String::new();
+    if escape {
+        preamble.push_str(HTML_ESCAPE_FN);
+    }
+    preamble.push_str("use ::std::fmt::Write as _;");
+
+    if let Some(literal) = parse_str_literal(template) {
+        let nodes = match parse_template(&literal) {
+            Ok(nodes) => nodes,
+            Err(err) => return compile_error(&err),
+        };
+        if let Some(err) = check_placeholder_coverage(&nodes, pairs) {
+            return compile_error(&err);
+        }
+
+        let body = emit_nodes(buf, &nodes, pairs, escape);
+        return format!("{{ {} {} }}", preamble, body)
+            .parse()
+            .expect("Failed to parse buffered template macro");
+    }
+
+    // Non-literal template: build the full string once, then append it to `buf`
+    // (no section support, since sections need the literal text at compile time).
+    let mut fallback = format!("let mut content_string = ({}).to_string();", template);
+    for (key, val) in pairs {
+        let pattern = "{{".to_string() + key + "}}";
+        let replacement = if escape {
+            let inner = val.strip_prefix("raw(").and_then(|v| v.strip_suffix(')'));
+            match inner {
+                Some(inner) => format!(r#"&format!("{{}}", {})"#, inner),
+                None => format!(r#"&__webio_html_escape(&format!("{{}}", {}))"#, val),
+            }
+        } else {
+            format!(r#"&format!("{{}}", {})"#, val)
+        };
+        fallback.push_str(&format!(
+            r#"content_string = content_string.replace("{}", {});"#,
+            pattern, replacement
+        ));
+    }
+    fallback.push_str(&format!("({}).push_str(&content_string);", buf));
+
+    format!("{{ {} {} }}", preamble, fallback)
+        .parse()
+        .expect("Failed to parse buffered template macro")
+}
+
+/// Emits a `write!(buf, "{}", ...)` statement for a single placeholder value,
+/// honoring the `raw(...)` escaping opt-out when `escape` is set.
+fn buffered_write(buf: &str, val: &str, escape: bool) -> String {
+    if escape {
+        match val.strip_prefix("raw(").and_then(|v| v.strip_suffix(')')) {
+            Some(inner) => format!(r#"write!({}, "{{}}", {}).unwrap();"#, buf, inner),
+            None => format!(
+                r#"write!({}, "{{}}", __webio_html_escape(&format!("{{}}", {}))).unwrap();"#,
+                buf, val
+            ),
+        }
+    } else {
+        format!(r#"write!({}, "{{}}", {}).unwrap();"#, buf, val)
+    }
+}
+
+/// Splits `input` into its top-level comma-separated argument segments. Unlike
+/// `input.to_string().split(',')`, a comma inside a string literal (a single
+/// `Literal` token) or a nested `(...)`/`[...]`/`{...}` group (e.g. `vec![a, b]`,
+/// `f(a, b)`) never ends a segment, since those commas only ever show up nested
+/// inside a `Group` token, never as a top-level `Punct` here.
+fn split_top_level_commas(input: TokenStream) -> Vec<TokenStream> {
+    let mut segments = Vec::new();
+    let mut current = Vec::new();
+
+    for tt in input {
+        match &tt {
+            TokenTree::Punct(p) if p.as_char() == ',' => {
+                segments.push(current.drain(..).collect());
+            }
+            _ => current.push(tt),
+        }
+    }
+    segments.push(current.into_iter().collect());
+
+    segments
+}
+
+/// Turns `key = val` argument segments, as produced by [`split_top_level_commas`],
+/// into `(key, val)` pairs, the same way [`replace!`] and [`html!`] do.
+fn pairs_from_segments(segments: &[TokenStream]) -> Vec<(String, String)> {
+    segments
+        .iter()
+        .filter_map(|segment| {
+            let text = segment.to_string();
+            let text = text.trim();
+            if text.is_empty() {
+                return None;
+            }
+            text.split_once('=')
+                .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Cross-checks the `{{key}}` placeholders (and `{{#if cond}}`/`{{#each items}}`
+/// bindings) found in a parsed literal template against the keys provided as
+/// macro arguments. Returns a human-readable error message when a placeholder
+/// has no matching key, or a key has no matching placeholder, so the caller can
+/// turn it into a `compile_error!`. Returns `None` when everything lines up.
+fn check_placeholder_coverage(nodes: &[TemplateNode], pairs: &[(String, String)]) -> Option<String> {
+    use std::collections::BTreeSet;
+
+    let mut placeholders = BTreeSet::new();
+    collect_placeholder_keys(nodes, &mut placeholders);
+    let provided: BTreeSet<&str> = pairs.iter().map(|(key, _)| key.as_str()).collect();
+
+    let mut problems = Vec::new();
+    for key in placeholders.difference(&provided) {
+        problems.push(format!("placeholder {{{{{}}}}} has no matching key", key));
+    }
+    for key in provided.difference(&placeholders) {
+        problems.push(format!("key `{}` has no matching placeholder", key));
+    }
+
+    if problems.is_empty() {
+        None
+    } else {
+        Some(problems.join("; "))
+    }
+}
+
+/// Collects every key a template tree expects to be bound via `key = expr`:
+/// plain `{{key}}` placeholders, and the `cond`/`items` identifiers of
+/// `{{#if}}`/`{{#each}}` sections. `this`/`.` is excluded everywhere it can
+/// appear (a plain placeholder, an `{{#if}}`'s `cond`, or an `{{#each}}`'s
+/// `items`), since it refers to the current `{{#each}}` element rather than a
+/// macro argument.
+fn collect_placeholder_keys<'a>(nodes: &'a [TemplateNode], keys: &mut std::collections::BTreeSet<&'a str>) {
+    for node in nodes {
+        match node {
+            TemplateNode::Literal(_) => {}
+            TemplateNode::Placeholder(key) => {
+                if key != "this" {
+                    keys.insert(key.as_str());
+                }
+            }
+            TemplateNode::If { cond, then_branch, else_branch } => {
+                if cond != "this" && cond != "." {
+                    keys.insert(cond.as_str());
+                }
+                collect_placeholder_keys(then_branch, keys);
+                collect_placeholder_keys(else_branch, keys);
+            }
+            TemplateNode::Each { items, body } => {
+                if items != "this" && items != "." {
+                    keys.insert(items.as_str());
+                }
+                collect_placeholder_keys(body, keys);
+            }
+        }
+    }
+}
+
+/// Builds a `compile_error!("...")` token stream carrying `message`.
+fn compile_error(message: &str) -> TokenStream {
+    format!("compile_error!(\"{}\")", escape_rust_string(message))
+        .parse()
+        .expect("Failed to parse compile_error!")
+}
+
+/// A node in a parsed template tree: literal text, a `{{key}}` placeholder, an
+/// `{{#if cond}}...{{else}}...{{/if}}` branch, or an `{{#each items}}...{{/each}}`
+/// loop. `cond` and `items` are the raw identifiers written in the section tag;
+/// they're resolved against the macro's `key = expr` arguments at codegen time.
+enum TemplateNode {
+    Literal(String),
+    Placeholder(String),
+    If {
+        cond: String,
+        then_branch: Vec<TemplateNode>,
+        else_branch: Vec<TemplateNode>,
+    },
+    Each {
+        items: String,
+        body: Vec<TemplateNode>,
+    },
+}
+
+/// One level of `{{#if}}`/`{{#each}}` nesting being accumulated while parsing.
+enum TemplateFrame {
+    If {
+        cond: String,
+        then_branch: Vec<TemplateNode>,
+        else_branch: Vec<TemplateNode>,
+        in_else: bool,
+    },
+    Each {
+        items: String,
+        body: Vec<TemplateNode>,
+    },
+}
+
+/// Parses a template's literal text into a tree of [`TemplateNode`]s, matching
+/// `{{#if}}`/`{{#each}}`/`{{else}}`/`{{/if}}`/`{{/each}}` tags with a stack.
+/// Plain `{{key}}` placeholders and literal text runs are handled the same way
+/// `replace!`'s original flat scanner did. Returns an error message describing
+/// any unbalanced or unrecognized section tag.
+fn parse_template(template: &str) -> Result<Vec<TemplateNode>, String> {
+    let mut root = Vec::new();
+    let mut stack: Vec<TemplateFrame> = Vec::new();
+    let mut rest = template;
+
+    loop {
+        match rest.find("{{") {
+            None => {
+                push_literal(&mut stack, &mut root, rest);
+                break;
+            }
+            Some(start) => {
+                if start > 0 {
+                    push_literal(&mut stack, &mut root, &rest[..start]);
+                }
+                let after_open = &rest[start + 2..];
+                let end = after_open
+                    .find("}}")
+                    .ok_or_else(|| "unterminated {{ in template".to_string())?;
+                let tag = after_open[..end].trim();
+                rest = &after_open[end + 2..];
+
+                if let Some(cond) = tag.strip_prefix("#if") {
+                    let cond = cond.trim().to_string();
+                    if (cond == "this" || cond == ".")
+                        && !stack.iter().any(|frame| matches!(frame, TemplateFrame::Each { .. }))
+                    {
+                        return Err(format!(
+                            "{{{{#if {}}}}} used outside an {{{{#each}}}} block",
+                            cond
+                        ));
+                    }
+                    stack.push(TemplateFrame::If {
+                        cond,
+                        then_branch: Vec::new(),
+                        else_branch: Vec::new(),
+                        in_else: false,
+                    });
+                } else if tag == "else" {
+                    match stack.last_mut() {
+                        Some(TemplateFrame::If { in_else, .. }) => *in_else = true,
+                        _ => return Err("{{else}} has no matching {{#if}}".to_string()),
+                    }
+                } else if tag == "/if" {
+                    match stack.pop() {
+                        Some(TemplateFrame::If { cond, then_branch, else_branch, .. }) => {
+                            push_node(
+                                &mut stack,
+                                &mut root,
+                                TemplateNode::If { cond, then_branch, else_branch },
+                            );
+                        }
+                        _ => return Err("{{/if}} has no matching {{#if}}".to_string()),
+                    }
+                } else if let Some(items) = tag.strip_prefix("#each") {
+                    let items = items.trim().to_string();
+                    if (items == "this" || items == ".")
+                        && !stack.iter().any(|frame| matches!(frame, TemplateFrame::Each { .. }))
+                    {
+                        return Err(format!(
+                            "{{{{#each {}}}}} used outside an {{{{#each}}}} block",
+                            items
+                        ));
+                    }
+                    stack.push(TemplateFrame::Each { items, body: Vec::new() });
+                } else if tag == "/each" {
+                    match stack.pop() {
+                        Some(TemplateFrame::Each { items, body }) => {
+                            push_node(&mut stack, &mut root, TemplateNode::Each { items, body });
+                        }
+                        _ => return Err("{{/each}} has no matching {{#each}}".to_string()),
+                    }
+                } else if tag == "this" || tag == "." {
+                    if !stack.iter().any(|frame| matches!(frame, TemplateFrame::Each { .. })) {
+                        return Err(format!("{{{{{}}}}} used outside an {{{{#each}}}} block", tag));
+                    }
+                    push_node(&mut stack, &mut root, TemplateNode::Placeholder("this".to_string()));
+                } else {
+                    push_node(&mut stack, &mut root, TemplateNode::Placeholder(tag.to_string()));
+                }
+            }
+        }
+    }
+
+    if let Some(frame) = stack.pop() {
+        return Err(match frame {
+            TemplateFrame::If { .. } => "unclosed {{#if}}: expected a matching {{/if}}".to_string(),
+            TemplateFrame::Each { .. } => "unclosed {{#each}}: expected a matching {{/each}}".to_string(),
+        });
+    }
+
+    Ok(root)
+}
+
+/// Appends `node` to whichever branch is currently open: the innermost stack
+/// frame's active branch, or `root` when no section is open.
+fn push_node(stack: &mut [TemplateFrame], root: &mut Vec<TemplateNode>, node: TemplateNode) {
+    match stack.last_mut() {
+        Some(TemplateFrame::If { then_branch, else_branch, in_else, .. }) => {
+            if *in_else {
+                else_branch.push(node);
+            } else {
+                then_branch.push(node);
+            }
+        }
+        Some(TemplateFrame::Each { body, .. }) => body.push(node),
+        None => root.push(node),
+    }
+}
+
+/// Appends a non-empty literal text run via [`push_node`].
+fn push_literal(stack: &mut [TemplateFrame], root: &mut Vec<TemplateNode>, text: &str) {
+    if !text.is_empty() {
+        push_node(stack, root, TemplateNode::Literal(text.to_string()));
+    }
+}
+
+/// Lowers a parsed template tree into code that builds its output into `buf`
+/// (a `&mut String`/`std::fmt::Write` expression): literal text becomes
+/// `push_str`, placeholders become `write!`, `{{#if}}` becomes an `if`/`else`,
+/// and `{{#each}}` becomes a `for this in ...` loop.
+fn emit_nodes(buf: &str, nodes: &[TemplateNode], pairs: &[(String, String)], escape: bool) -> String {
+    let mut code = String::new();
+
+    for node in nodes {
+        match node {
+            TemplateNode::Literal(text) => {
+                code.push_str(&format!(
+                    "({}).push_str(\"{}\");",
+                    buf,
+                    escape_rust_string(text)
+                ));
+            }
+            TemplateNode::Placeholder(key) => {
+                code.push_str(&emit_placeholder(buf, key, pairs, escape));
+            }
+            TemplateNode::If { cond, then_branch, else_branch } => {
+                let cond_val = if cond == "this" || cond == "." {
+                    "this"
+                } else {
+                    lookup_key(pairs, cond).unwrap_or("false")
+                };
+                code.push_str(&format!(
+                    "if ({}) {{ {} }} else {{ {} }}",
+                    cond_val,
+                    emit_nodes(buf, then_branch, pairs, escape),
+                    emit_nodes(buf, else_branch, pairs, escape)
+                ));
+            }
+            TemplateNode::Each { items, body } => {
+                let items_val = if items == "this" || items == "." {
+                    "this"
+                } else {
+                    lookup_key(pairs, items).unwrap_or("::std::iter::empty::<()>()")
+                };
+                code.push_str(&format!(
+                    "for this in ({}) {{ {} }}",
+                    items_val,
+                    emit_nodes(buf, body, pairs, escape)
+                ));
+            }
+        }
+    }
+
+    code
+}
+
+/// Emits the code for a single `{{key}}` placeholder: `{{this}}`/`{{.}}` writes
+/// the current `{{#each}}` loop variable, anything else looks up its bound value
+/// among `pairs` (guaranteed present once [`check_placeholder_coverage`] passes).
+fn emit_placeholder(buf: &str, key: &str, pairs: &[(String, String)], escape: bool) -> String {
+    if key == "this" {
+        return buffered_write(buf, "this", escape);
+    }
+    match lookup_key(pairs, key) {
+        Some(val) => buffered_write(buf, val, escape),
+        // No matching key: leave the placeholder untouched, as `replace!` does.
+        None => format!("({}).push_str(\"{{{{{}}}}}\");", buf, key),
+    }
+}
+
+/// Looks up `key`'s bound value among `key = val` pairs.
+fn lookup_key<'a>(pairs: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+}
+
+/// Escapes a template's literal text for embedding inside a generated `"..."`
+/// string literal (used when emitting `push_str` calls).
+fn escape_rust_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Parses a Rust string-literal token (as produced by `TokenStream::to_string()`)
+/// into its actual string value. Supports plain `"..."` literals (with `\\`, `\"`,
+/// `\n`, `\t`, `\r`, `\0` escapes) and raw `r"..."` / `r#"..."#` literals. Returns
+/// `None` for anything else (a variable or expression), so callers can fall back
+/// to runtime behavior.
+fn parse_str_literal(tok: &str) -> Option<String> {
+    let tok = tok.trim();
+
+    if let Some(rest) = tok.strip_prefix('r') {
+        let hashes = rest.chars().take_while(|&c| c == '#').count();
+        let body = &rest[hashes..];
+        let closing = format!("\"{}", "#".repeat(hashes));
+        if !body.starts_with('"') || !body.ends_with(&closing) || body.len() < 1 + closing.len() {
+            return None;
+        }
+        return Some(body[1..body.len() - closing.len()].to_string());
+    }
+
+    if tok.starts_with('"') && tok.ends_with('"') && tok.len() >= 2 {
+        let inner = &tok[1..tok.len() - 1];
+        let mut out = String::with_capacity(inner.len());
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some('r') => out.push('\r'),
+                Some('0') => out.push('\0'),
+                Some(other) => out.push(other),
+                None => {}
+            }
+        }
+        return Some(out);
+    }
+
+    None
 }
\ No newline at end of file